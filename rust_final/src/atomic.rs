@@ -0,0 +1,110 @@
+//! Crash-safe file writes, shared by the one-shot/`--watch` output path and the driver's
+//! aggregated result output.
+
+use std::fs::{self, File};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Writes `contents` to `path` without ever exposing a partial file to readers.
+///
+/// The data is written to a `.tmp` sibling of `path` (created with create-new semantics
+/// and, on Unix, mode `0o600`), `sync_data`'d to disk, then renamed over `path`. The temp
+/// file is removed on any error before the rename so failed writes don't leave litter, and
+/// any stale `.tmp` left behind by a previous crash (between creating it and the rename)
+/// is cleared before we try to create a fresh one, so a single crashed write can't wedge
+/// every write after it.
+pub fn write_atomic(path: &str, contents: &[u8]) -> std::io::Result<()> {
+    let tmp_path = format!("{}.tmp", path);
+    let _ = fs::remove_file(&tmp_path);
+
+    let mut open_options = File::options();
+    open_options.write(true).create_new(true);
+    #[cfg(unix)]
+    open_options.mode(0o600);
+
+    let result = (|| -> std::io::Result<()> {
+        let mut tmp_file = open_options.open(&tmp_path)?;
+        tmp_file.write_all(contents)?;
+        tmp_file.sync_data()?;
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+
+    fs::rename(&tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unique scratch file path per test, cleaned up (along with any `.tmp` sibling) on
+    /// drop.
+    struct TempPath(std::path::PathBuf);
+
+    impl TempPath {
+        fn new(label: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "website_checker_atomic_test_{}_{}_{:?}",
+                label,
+                std::process::id(),
+                std::thread::current().id()
+            ));
+            let _ = fs::remove_file(&path);
+            let _ = fs::remove_file(format!("{}.tmp", path.to_str().unwrap()));
+            TempPath(path)
+        }
+
+        fn as_str(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+
+        fn tmp_path(&self) -> String {
+            format!("{}.tmp", self.as_str())
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+            let _ = fs::remove_file(self.tmp_path());
+        }
+    }
+
+    #[test]
+    fn writes_contents_and_renames_into_place() {
+        let path = TempPath::new("write");
+        write_atomic(path.as_str(), b"hello").unwrap();
+        assert_eq!(fs::read(path.as_str()).unwrap(), b"hello");
+        assert!(!std::path::Path::new(&path.tmp_path()).exists());
+    }
+
+    #[test]
+    fn a_stale_tmp_file_from_a_prior_crash_is_cleared_before_writing() {
+        let path = TempPath::new("stale");
+        fs::write(path.tmp_path(), b"leftover from a crashed write").unwrap();
+
+        write_atomic(path.as_str(), b"fresh").unwrap();
+
+        assert_eq!(fs::read(path.as_str()).unwrap(), b"fresh");
+        assert!(!std::path::Path::new(&path.tmp_path()).exists());
+    }
+
+    #[test]
+    fn a_failed_write_leaves_no_tmp_file_behind() {
+        // A path whose parent directory doesn't exist makes the rename (and, on most
+        // platforms, the initial open) fail, without relying on any platform-specific
+        // permission trick.
+        let bogus_path = std::env::temp_dir()
+            .join("website_checker_atomic_test_missing_dir_does_not_exist")
+            .join("status.json");
+        let bogus_path = bogus_path.to_str().unwrap();
+
+        assert!(write_atomic(bogus_path, b"data").is_err());
+        assert!(!std::path::Path::new(&format!("{}.tmp", bogus_path)).exists());
+    }
+}
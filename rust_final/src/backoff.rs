@@ -0,0 +1,63 @@
+//! Exponential backoff with jitter, shared by retry-within-a-check and the `--watch`
+//! scheduler's per-URL rescheduling.
+
+use rand::Rng;
+use std::time::Duration;
+
+/// Doubles `base` once per failed attempt, capped at `cap`.
+pub fn exponential_delay(consecutive_failures: u32, base: Duration, cap: Duration) -> Duration {
+    let mut delay = base;
+    for _ in 0..consecutive_failures {
+        delay = match delay.checked_mul(2) {
+            Some(doubled) if doubled < cap => doubled,
+            _ => return cap,
+        };
+    }
+    delay
+}
+
+/// Applies up to ±10% random jitter to `delay`, to avoid many URLs waking in lockstep.
+pub fn with_jitter(delay: Duration) -> Duration {
+    let jitter_fraction = rand::thread_rng().gen_range(-0.1..=0.1);
+    let millis = delay.as_millis() as f64 * (1.0 + jitter_fraction);
+    Duration::from_millis(millis.max(0.0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_failures_is_base_delay() {
+        let base = Duration::from_secs(30);
+        let cap = Duration::from_secs(3600);
+        assert_eq!(exponential_delay(0, base, cap), base);
+    }
+
+    #[test]
+    fn doubles_per_consecutive_failure() {
+        let base = Duration::from_secs(30);
+        let cap = Duration::from_secs(3600);
+        assert_eq!(exponential_delay(1, base, cap), Duration::from_secs(60));
+        assert_eq!(exponential_delay(2, base, cap), Duration::from_secs(120));
+        assert_eq!(exponential_delay(3, base, cap), Duration::from_secs(240));
+    }
+
+    #[test]
+    fn delay_never_exceeds_cap() {
+        let base = Duration::from_secs(30);
+        let cap = Duration::from_secs(100);
+        assert_eq!(exponential_delay(10, base, cap), cap);
+        assert_eq!(exponential_delay(u32::MAX, base, cap), cap);
+    }
+
+    #[test]
+    fn jitter_stays_within_ten_percent() {
+        let delay = Duration::from_secs(100);
+        for _ in 0..1000 {
+            let jittered = with_jitter(delay);
+            assert!(jittered >= Duration::from_millis(90_000));
+            assert!(jittered <= Duration::from_millis(110_000));
+        }
+    }
+}
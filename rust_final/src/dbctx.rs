@@ -0,0 +1,209 @@
+//! Small SQLite-backed context for persisting check results across runs.
+//!
+//! Opens (and migrates, if empty) a database with a `urls` table and a `checks` table
+//! keyed by `(url, timestamp)`, then exposes helpers to insert a completed check and to
+//! query rolling uptime and latency percentiles for a given URL.
+
+use rusqlite::{params, Connection};
+use std::time::{Duration, SystemTime};
+
+pub struct DbCtx {
+    conn: Connection,
+}
+
+/// One row of check history as read back out of the `checks` table.
+pub struct CheckRow {
+    pub timestamp: i64,
+    pub status_code: Option<i64>,
+    pub error: Option<String>,
+    pub response_time_ms: i64,
+}
+
+impl DbCtx {
+    /// Opens `path`, creating and migrating the schema if the database is empty.
+    pub fn open(path: &str) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS urls (
+                url TEXT PRIMARY KEY
+            );
+            CREATE TABLE IF NOT EXISTS checks (
+                url TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                status_code INTEGER,
+                error TEXT,
+                response_time_ms INTEGER NOT NULL,
+                PRIMARY KEY (url, timestamp),
+                FOREIGN KEY (url) REFERENCES urls (url)
+            );",
+        )?;
+        Ok(DbCtx { conn })
+    }
+
+    /// Records one completed check, inserting the URL into `urls` on first sight.
+    pub fn record_check(
+        &self,
+        url: &str,
+        timestamp: SystemTime,
+        action_status: &Result<u16, String>,
+        response_time: Duration,
+    ) -> rusqlite::Result<()> {
+        let timestamp_secs = timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+
+        let (status_code, error) = match action_status {
+            Ok(code) => (Some(*code as i64), None),
+            Err(err) => (None, Some(err.clone())),
+        };
+
+        self.conn.execute(
+            "INSERT OR REPLACE INTO urls (url) VALUES (?1)",
+            params![url],
+        )?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO checks (url, timestamp, status_code, error, response_time_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                url,
+                timestamp_secs,
+                status_code,
+                error,
+                response_time.as_millis() as i64
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Returns every recorded check for `url`, most recent first.
+    pub fn history(&self, url: &str) -> rusqlite::Result<Vec<CheckRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp, status_code, error, response_time_ms
+             FROM checks WHERE url = ?1 ORDER BY timestamp DESC",
+        )?;
+        let rows = stmt
+            .query_map(params![url], |row| {
+                Ok(CheckRow {
+                    timestamp: row.get(0)?,
+                    status_code: row.get(1)?,
+                    error: row.get(2)?,
+                    response_time_ms: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    }
+
+    /// Returns every check for `url` at or after `since_secs` (Unix time), oldest first.
+    pub fn checks_since(&self, url: &str, since_secs: i64) -> rusqlite::Result<Vec<CheckRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp, status_code, error, response_time_ms
+             FROM checks WHERE url = ?1 AND timestamp >= ?2 ORDER BY timestamp ASC",
+        )?;
+        let rows = stmt
+            .query_map(params![url, since_secs], |row| {
+                Ok(CheckRow {
+                    timestamp: row.get(0)?,
+                    status_code: row.get(1)?,
+                    error: row.get(2)?,
+                    response_time_ms: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    }
+}
+
+/// Uptime ratio plus p50/p95 latency over a set of check rows.
+pub struct UptimeSummary {
+    pub total_checks: usize,
+    pub successful_checks: usize,
+    pub uptime_ratio: f64,
+    pub p50_ms: i64,
+    pub p95_ms: i64,
+}
+
+/// Computes uptime ratio and latency percentiles from a slice of checks.
+pub fn summarize_uptime(rows: &[CheckRow]) -> UptimeSummary {
+    let total_checks = rows.len();
+    let successful_checks = rows
+        .iter()
+        .filter(|r| r.status_code.is_some_and(|c| (200..400).contains(&c)))
+        .count();
+
+    let mut latencies: Vec<i64> = rows.iter().map(|r| r.response_time_ms).collect();
+    latencies.sort_unstable();
+
+    let percentile = |p: f64| -> i64 {
+        if latencies.is_empty() {
+            return 0;
+        }
+        let idx = ((latencies.len() as f64 - 1.0) * p).round() as usize;
+        latencies[idx]
+    };
+
+    UptimeSummary {
+        total_checks,
+        successful_checks,
+        uptime_ratio: if total_checks == 0 {
+            0.0
+        } else {
+            successful_checks as f64 / total_checks as f64
+        },
+        p50_ms: percentile(0.50),
+        p95_ms: percentile(0.95),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(status_code: Option<i64>, response_time_ms: i64) -> CheckRow {
+        CheckRow {
+            timestamp: 0,
+            status_code,
+            error: status_code.is_none().then(|| "boom".to_string()),
+            response_time_ms,
+        }
+    }
+
+    #[test]
+    fn empty_rows_summarize_to_zero() {
+        let summary = summarize_uptime(&[]);
+        assert_eq!(summary.total_checks, 0);
+        assert_eq!(summary.successful_checks, 0);
+        assert_eq!(summary.uptime_ratio, 0.0);
+        assert_eq!(summary.p50_ms, 0);
+        assert_eq!(summary.p95_ms, 0);
+    }
+
+    #[test]
+    fn uptime_ratio_counts_2xx_3xx_as_successful() {
+        let rows = vec![
+            row(Some(200), 10),
+            row(Some(404), 20),
+            row(Some(301), 30),
+            row(None, 40),
+        ];
+        let summary = summarize_uptime(&rows);
+        assert_eq!(summary.total_checks, 4);
+        assert_eq!(summary.successful_checks, 2);
+        assert_eq!(summary.uptime_ratio, 0.5);
+    }
+
+    #[test]
+    fn percentiles_are_computed_over_sorted_latencies() {
+        let rows = vec![
+            row(Some(200), 100),
+            row(Some(200), 10),
+            row(Some(200), 50),
+            row(Some(200), 40),
+            row(Some(200), 20),
+        ];
+        let summary = summarize_uptime(&rows);
+        assert_eq!(summary.p50_ms, 40);
+        assert_eq!(summary.p95_ms, 100);
+    }
+}
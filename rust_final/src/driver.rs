@@ -0,0 +1,300 @@
+//! Coordinator side of the distributed driver/runner architecture.
+//!
+//! The driver hands out one job per URL to whichever runner asks first (`GET /job`,
+//! long-polled) and accepts result submissions (`POST /result`). Every request must
+//! carry a valid HMAC-SHA256 `X-Signature` header over its body, computed with the
+//! shared pre-shared key, or it is rejected. Results are aggregated, tagged with the
+//! reporting runner, and written out once every URL has a result.
+//!
+//! A job handed out by `GET /job` is leased, not given away: if no result comes back
+//! within [`job_lease`] of it being handed out, it's put back on the queue for another
+//! runner to pick up, so a runner that crashes or is killed mid-check doesn't strand its
+//! URL forever. An overall deadline bounds the whole run in case too few runners show up
+//! to ever finish; past it, the driver returns whatever partial results it has.
+//!
+//! This is a deliberately small hand-rolled HTTP/1.1 server (one thread per connection,
+//! `Content-Length` bodies only) rather than a full web framework, in keeping with the
+//! rest of this tool's dependency-light style.
+
+use crate::protocol::{self, JobResult, RequestedJob};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How many lease cycles worth of time the driver allows the whole run before giving up
+/// and returning partial results.
+const MAX_RUNTIME_LEASES: u32 = 4;
+
+/// How often the accept loop checks the overall deadline while waiting for a connection.
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long a `GET /job` request is held open waiting for a job before answering 204.
+const LONG_POLL_WAIT: Duration = Duration::from_secs(10);
+
+struct DriverState {
+    psk: Vec<u8>,
+    queue: Mutex<VecDeque<String>>,
+    /// URLs currently leased to a runner, keyed by URL, with the instant the lease
+    /// expires and the job should be put back on the queue.
+    leased: Mutex<HashMap<String, Instant>>,
+    lease: Duration,
+    timeout_secs: u64,
+    retries: u32,
+    results: Mutex<Vec<JobResult>>,
+}
+
+impl DriverState {
+    /// Moves any URL whose lease has expired back onto the queue.
+    fn reclaim_expired_leases(&self) {
+        let now = Instant::now();
+        let mut leased = self.leased.lock().unwrap();
+        let expired: Vec<String> = leased
+            .iter()
+            .filter(|(_, &deadline)| deadline <= now)
+            .map(|(url, _)| url.clone())
+            .collect();
+        if expired.is_empty() {
+            return;
+        }
+        let mut queue = self.queue.lock().unwrap();
+        for url in expired {
+            leased.remove(&url);
+            eprintln!("Driver: lease expired for {}, requeuing", url);
+            queue.push_back(url);
+        }
+    }
+
+    /// Number of distinct URLs that have at least one result.
+    fn urls_with_results(&self) -> usize {
+        let results = self.results.lock().unwrap();
+        let urls: HashSet<&str> = results.iter().map(|r| r.url.as_str()).collect();
+        urls.len()
+    }
+}
+
+/// How long a runner is given to report back on a handed-out job before it's requeued:
+/// enough time for every retry at the full per-check timeout, plus slack for scheduling
+/// and network round-trips.
+fn job_lease(timeout_secs: u64, retries: u32) -> Duration {
+    Duration::from_secs(timeout_secs * (retries as u64 + 1) + 30)
+}
+
+/// Runs the driver: serves `GET /job` and `POST /result` on `listen_addr` until every
+/// URL in `urls` has a result or the overall deadline passes, then returns the
+/// aggregated, runner-tagged results gathered so far.
+pub fn run_driver(
+    listen_addr: &str,
+    psk: Vec<u8>,
+    urls: Vec<String>,
+    timeout_secs: u64,
+    retries: u32,
+) -> Vec<JobResult> {
+    let expected = urls.iter().collect::<HashSet<_>>().len();
+    let lease = job_lease(timeout_secs, retries);
+    let state = Arc::new(DriverState {
+        psk,
+        queue: Mutex::new(urls.into_iter().collect()),
+        leased: Mutex::new(HashMap::new()),
+        lease,
+        timeout_secs,
+        retries,
+        results: Mutex::new(Vec::new()),
+    });
+
+    let listener = TcpListener::bind(listen_addr).expect("Failed to bind driver listen address");
+    listener
+        .set_nonblocking(true)
+        .expect("Failed to set driver listener non-blocking");
+    println!("Driver listening on {} for {} URL(s)", listen_addr, expected);
+
+    let deadline = Instant::now() + lease * MAX_RUNTIME_LEASES;
+    let mut connection_handles = Vec::new();
+    loop {
+        if state.urls_with_results() >= expected {
+            break;
+        }
+        if Instant::now() >= deadline {
+            eprintln!(
+                "Driver: overall deadline reached with {}/{} result(s); returning partial results",
+                state.urls_with_results(),
+                expected
+            );
+            break;
+        }
+
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let state_clone = Arc::clone(&state);
+                connection_handles.push(thread::spawn(move || {
+                    handle_connection(stream, &state_clone)
+                }));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                state.reclaim_expired_leases();
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+            Err(_) => continue,
+        }
+    }
+
+    for handle in connection_handles {
+        let _ = handle.join();
+    }
+
+    Arc::try_unwrap(state)
+        .unwrap_or_else(|_| panic!("driver state still shared after completion"))
+        .results
+        .into_inner()
+        .unwrap()
+}
+
+fn handle_connection(mut stream: TcpStream, state: &DriverState) {
+    let (method, path, signature) = match read_request(&stream) {
+        Some(parts) => parts,
+        None => return,
+    };
+
+    let response = match (method.as_str(), path.as_str()) {
+        ("GET", "/job") => handle_get_job(state, &signature),
+        ("POST", "/result") => handle_post_result(state, &signature),
+        _ => http_response(404, "not found"),
+    };
+
+    let _ = stream.write_all(response.as_bytes());
+}
+
+fn handle_get_job(state: &DriverState, signature: &HeaderSignature) -> String {
+    if !signature.is_valid(&state.psk) {
+        return http_response(401, "invalid signature");
+    }
+
+    let poll_deadline = Instant::now() + LONG_POLL_WAIT;
+    let url = loop {
+        state.reclaim_expired_leases();
+        if let Some(url) = state.queue.lock().unwrap().pop_front() {
+            break Some(url);
+        }
+        if Instant::now() >= poll_deadline {
+            break None;
+        }
+        thread::sleep(ACCEPT_POLL_INTERVAL);
+    };
+    match url {
+        Some(url) => {
+            state
+                .leased
+                .lock()
+                .unwrap()
+                .insert(url.clone(), Instant::now() + state.lease);
+            let job = RequestedJob {
+                url,
+                timeout_secs: state.timeout_secs,
+                retries: state.retries,
+            };
+            let body = serde_json::to_string(&job).expect("RequestedJob always serializes");
+            http_response(200, &body)
+        }
+        None => http_response(204, ""),
+    }
+}
+
+fn handle_post_result(state: &DriverState, signature: &HeaderSignature) -> String {
+    if !signature.is_valid(&state.psk) {
+        return http_response(401, "invalid signature");
+    }
+
+    let result: JobResult = match serde_json::from_slice(&signature.body) {
+        Ok(r) => r,
+        Err(_) => return http_response(400, "invalid JobResult body"),
+    };
+
+    state.leased.lock().unwrap().remove(&result.url);
+    state.results.lock().unwrap().push(result);
+    http_response(200, "ok")
+}
+
+/// Carries the raw body bytes alongside the `X-Signature` header so each handler can
+/// both inspect the payload and verify it was signed with the shared PSK.
+struct HeaderSignature {
+    signature: Option<String>,
+    body: Vec<u8>,
+}
+
+impl HeaderSignature {
+    fn is_valid(&self, psk: &[u8]) -> bool {
+        match &self.signature {
+            Some(sig) => protocol::verify(psk, &self.body, sig),
+            None => false,
+        }
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Option<(String, String, HeaderSignature)> {
+    let mut reader = BufReader::new(stream.try_clone().ok()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut content_length = 0usize;
+    let mut signature = None;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            match key.trim().to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                "x-signature" => signature = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    let mut body = vec![0u8; content_length];
+    if content_length > 0 {
+        reader.read_exact(&mut body).ok()?;
+    }
+
+    Some((method, path, HeaderSignature { signature, body }))
+}
+
+fn http_response(status: u16, body: &str) -> String {
+    let reason = match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        _ => "Unknown",
+    };
+    format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nContent-Type: application/json\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )
+}
+
+/// Writes aggregated, runner-tagged results to `output_path` atomically, through the
+/// same [`crate::output::StatusRecord`]/[`crate::output::render`] machinery as the
+/// single-machine output, so the driver's `--format json/csv/prometheus` output matches
+/// the flat shape used everywhere else, with a `runner_id` field per entry so
+/// region-specific outages show up in the output.
+pub fn write_results(results: &[JobResult], output_path: &str, format: crate::output::Format) {
+    let records: Vec<crate::output::StatusRecord> = results.iter().map(Into::into).collect();
+    let body = crate::output::render(&records, format);
+    if let Err(e) = crate::atomic::write_atomic(output_path, body.as_bytes()) {
+        panic!("Failed to write {}: {}", output_path, e);
+    }
+    println!("Results written to {}", output_path);
+}
@@ -1,32 +1,51 @@
+mod atomic;
+mod backoff;
+mod dbctx;
+mod driver;
+mod notifier;
+mod output;
+mod protocol;
+mod runner;
+mod scheduler;
+
+use dbctx::DbCtx;
+use notifier::State;
 use reqwest::blocking::Client;
+use scheduler::Scheduler;
+use std::collections::HashMap;
 use std::env;
-use std::fs::{self, File};
-use std::io::Write;
+use std::fs;
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant, SystemTime};
 
 #[derive(Debug)]
-struct WebsiteStatus {
-    url: String,
-    action_status: Result<u16, String>,
-    response_time: Duration,
-    timestamp: SystemTime,
+pub(crate) struct WebsiteStatus {
+    pub(crate) url: String,
+    pub(crate) action_status: Result<u16, String>,
+    pub(crate) response_time: Duration,
+    pub(crate) timestamp: SystemTime,
 }
 
-fn process_url(
+pub(crate) fn process_url(
     url: &str,
     client: &Client,
     timeout_secs: u64,
     retries: u32,
 ) -> WebsiteStatus {
+    const RETRY_BACKOFF_BASE: Duration = Duration::from_millis(100);
+    const RETRY_BACKOFF_CAP: Duration = Duration::from_secs(2);
+
     let start_time = Instant::now();
     let timestamp = SystemTime::now();
     let mut attempts = 0;
     let mut last_error = String::new();
 
     while attempts <= retries {
-        let result = client.get(url).send();
+        let result = client
+            .get(url)
+            .timeout(Duration::from_secs(timeout_secs))
+            .send();
         match result {
             Ok(resp) => {
                 let status = resp.status();
@@ -39,7 +58,12 @@ fn process_url(
             }
             Err(e) => {
                 last_error = format!("{}", e);
-                thread::sleep(Duration::from_millis(100));
+                let delay = backoff::with_jitter(backoff::exponential_delay(
+                    attempts,
+                    RETRY_BACKOFF_BASE,
+                    RETRY_BACKOFF_CAP,
+                ));
+                thread::sleep(delay);
             }
         }
         attempts += 1;
@@ -53,47 +77,50 @@ fn process_url(
     }
 }
 
-fn write_status_json(results: &[WebsiteStatus]) {
-    let mut json_output = String::from("[\n");
-
-    for (i, result) in results.iter().enumerate() {
-        let status_str = match &result.action_status {
-            Ok(code) => format!("{}", code),
-            Err(err) => format!("\"{}\"", err.replace('"', "'")),
-        };
-
-        let entry = format!(
-            "  {{\n    \"url\": \"{}\",\n    \"status\": {},\n    \"response_time_ms\": {},\n    \"timestamp\": {}\n  }}",
-            result.url,
-            status_str,
-            result.response_time.as_millis(),
-            result.timestamp.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs()
-        );
+/// Writes `results` to `output_path` atomically in the given `format`. `--watch` uses this
+/// for every format, `ndjson` included; a one-shot `ndjson` run streams its lines instead
+/// (see [`output::open_ndjson`] and [`output::append_ndjson_line`]).
+fn write_status_json<'a>(
+    results: impl IntoIterator<Item = &'a WebsiteStatus>,
+    output_path: &str,
+    format: output::Format,
+) {
+    let records: Vec<output::StatusRecord> = results.into_iter().map(Into::into).collect();
+    let rendered = output::render(&records, format);
 
-        json_output.push_str(&entry);
-        if i != results.len() - 1 {
-            json_output.push_str(",\n");
-        } else {
-            json_output.push('\n');
-        }
+    if let Err(e) = atomic::write_atomic(output_path, rendered.as_bytes()) {
+        panic!("Failed to write {}: {}", output_path, e);
     }
 
-    json_output.push(']');
-
-    let mut file = File::create("status.json").expect("Failed to create status.json");
-    file.write_all(json_output.as_bytes())
-        .expect("Failed to write JSON");
+    println!("Results written to {}", output_path);
+}
 
-    println!("Results written to status.json");
+pub struct Config {
+    pub urls: Vec<String>,
+    pub workers: usize,
+    pub timeout_secs: u64,
+    pub retries: u32,
+    pub output_path: String,
+    pub db_path: Option<String>,
+    pub config_path: Option<String>,
+    pub watch: bool,
+    pub interval_secs: u64,
+    pub format: output::Format,
 }
 
-fn parse_args() -> Result<(Vec<String>, usize, u64, u32), String> {
+fn parse_args() -> Result<Config, String> {
     let args: Vec<String> = env::args().skip(1).collect();
     let mut urls = Vec::new();
     let mut workers = num_cpus::get();
     let mut timeout_secs = 5;
     let mut retries = 0;
     let mut file_path: Option<String> = None;
+    let mut output_path = "status.json".to_string();
+    let mut db_path: Option<String> = None;
+    let mut config_path: Option<String> = None;
+    let mut watch = false;
+    let mut interval_secs = 60;
+    let mut format = output::Format::Json;
 
     let mut i = 0;
     while i < args.len() {
@@ -117,6 +144,41 @@ fn parse_args() -> Result<(Vec<String>, usize, u64, u32), String> {
                 i += 1;
                 retries = args[i].parse().map_err(|_| "Invalid --retries value".to_string())?;
             }
+            "--output" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("Expected file path after --output".to_string());
+                }
+                output_path = args[i].clone();
+            }
+            "--db" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("Expected file path after --db".to_string());
+                }
+                db_path = Some(args[i].clone());
+            }
+            "--config" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("Expected file path after --config".to_string());
+                }
+                config_path = Some(args[i].clone());
+            }
+            "--watch" => {
+                watch = true;
+            }
+            "--interval" => {
+                i += 1;
+                interval_secs = args[i].parse().map_err(|_| "Invalid --interval value".to_string())?;
+            }
+            "--format" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("Expected format after --format".to_string());
+                }
+                format = output::Format::parse(&args[i])?;
+            }
             arg if !arg.starts_with("--") => {
                 urls.push(arg.to_string());
             }
@@ -139,69 +201,553 @@ fn parse_args() -> Result<(Vec<String>, usize, u64, u32), String> {
         return Err("No URLs provided. Use --file or provide URLs as arguments.".to_string());
     }
 
-    Ok((urls, workers, timeout_secs, retries))
+    Ok(Config {
+        urls,
+        workers,
+        timeout_secs,
+        retries,
+        output_path,
+        db_path,
+        config_path,
+        watch,
+        interval_secs,
+        format,
+    })
+}
+
+/// Parses a duration like `30s`, `15m`, `24h`, or `7d` as used by `--since`.
+fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    if input.len() < 2 {
+        return Err(format!("Invalid duration: {}", input));
+    }
+    let (number, unit) = input.split_at(input.len() - 1);
+    let amount: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid duration: {}", input))?;
+    let secs = match unit {
+        "s" => amount,
+        "m" => amount * 60,
+        "h" => amount * 3600,
+        "d" => amount * 86400,
+        _ => return Err(format!("Unknown duration unit in: {}", input)),
+    };
+    Ok(Duration::from_secs(secs))
+}
+
+/// Parses and runs `history <url> --db path.sqlite`, exiting the process on error.
+fn run_history_subcommand(args: &[String]) {
+    let mut url: Option<&str> = None;
+    let mut db_path: Option<&str> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--db" => {
+                i += 1;
+                db_path = args.get(i).map(String::as_str);
+            }
+            arg => url = Some(arg),
+        }
+        i += 1;
+    }
+
+    let (Some(url), Some(db_path)) = (url, db_path) else {
+        eprintln!("Usage: website_checker history <url> --db path.sqlite");
+        std::process::exit(2);
+    };
+
+    if let Err(e) = run_history_command(db_path, url) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Parses and runs `uptime <url> --db path.sqlite --since <duration>`, exiting on error.
+fn run_uptime_subcommand(args: &[String]) {
+    let mut url: Option<&str> = None;
+    let mut db_path: Option<&str> = None;
+    let mut since_str: Option<&str> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--db" => {
+                i += 1;
+                db_path = args.get(i).map(String::as_str);
+            }
+            "--since" => {
+                i += 1;
+                since_str = args.get(i).map(String::as_str);
+            }
+            arg => url = Some(arg),
+        }
+        i += 1;
+    }
+
+    let (Some(url), Some(db_path), Some(since_str)) = (url, db_path, since_str) else {
+        eprintln!("Usage: website_checker uptime <url> --db path.sqlite --since <duration>");
+        std::process::exit(2);
+    };
+
+    let since = match parse_duration(since_str) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = run_uptime_command(db_path, url, since) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Parses and runs `driver --listen addr --psk-file path [--output path] [--db path]
+/// [--format fmt] [--timeout S] [--retries N] URL...`, exiting the process on error.
+fn run_driver_subcommand(args: &[String]) {
+    let mut listen: Option<&str> = None;
+    let mut psk_file: Option<&str> = None;
+    let mut output_path = "status.json".to_string();
+    let mut db_path: Option<&str> = None;
+    let mut format = output::Format::Json;
+    let mut timeout_secs = 5u64;
+    let mut retries = 0u32;
+    let mut urls = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--listen" => {
+                i += 1;
+                listen = args.get(i).map(String::as_str);
+            }
+            "--psk-file" => {
+                i += 1;
+                psk_file = args.get(i).map(String::as_str);
+            }
+            "--output" => {
+                i += 1;
+                if let Some(path) = args.get(i) {
+                    output_path = path.clone();
+                }
+            }
+            "--db" => {
+                i += 1;
+                db_path = args.get(i).map(String::as_str);
+            }
+            "--format" => {
+                i += 1;
+                if let Some(v) = args.get(i) {
+                    format = output::Format::parse(v).unwrap_or_else(|e| {
+                        eprintln!("{}", e);
+                        std::process::exit(2);
+                    });
+                }
+            }
+            "--timeout" => {
+                i += 1;
+                if let Some(v) = args.get(i) {
+                    timeout_secs = v.parse().unwrap_or(timeout_secs);
+                }
+            }
+            "--retries" => {
+                i += 1;
+                if let Some(v) = args.get(i) {
+                    retries = v.parse().unwrap_or(retries);
+                }
+            }
+            arg => urls.push(arg.to_string()),
+        }
+        i += 1;
+    }
+
+    let (Some(listen), Some(psk_file)) = (listen, psk_file) else {
+        eprintln!("Usage: website_checker driver --listen addr --psk-file path [--output path] [--db path] [--format fmt] [--timeout S] [--retries N] URL...");
+        std::process::exit(2);
+    };
+    if urls.is_empty() {
+        eprintln!("No URLs provided to drive.");
+        std::process::exit(2);
+    }
+
+    let psk = read_psk(psk_file);
+    let results = driver::run_driver(listen, psk, urls, timeout_secs, retries);
+
+    if let Some(db_path) = db_path {
+        let db = DbCtx::open(db_path).unwrap_or_else(|e| {
+            eprintln!("Failed to open {}: {}", db_path, e);
+            std::process::exit(1);
+        });
+        for result in &results {
+            let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(result.timestamp);
+            let response_time = Duration::from_millis(result.response_time_ms);
+            if let Err(e) = db.record_check(&result.url, timestamp, &result.status, response_time) {
+                eprintln!("Failed to record check for {} in {}: {}", result.url, db_path, e);
+            }
+        }
+    }
+
+    driver::write_results(&results, &output_path, format);
+}
+
+/// Parses and runs `runner --driver url --psk-file path --runner-id id`, exiting on
+/// error.
+fn run_runner_subcommand(args: &[String]) {
+    let mut driver_url: Option<&str> = None;
+    let mut psk_file: Option<&str> = None;
+    let mut runner_id: Option<&str> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--driver" => {
+                i += 1;
+                driver_url = args.get(i).map(String::as_str);
+            }
+            "--psk-file" => {
+                i += 1;
+                psk_file = args.get(i).map(String::as_str);
+            }
+            "--runner-id" => {
+                i += 1;
+                runner_id = args.get(i).map(String::as_str);
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let (Some(driver_url), Some(psk_file), Some(runner_id)) = (driver_url, psk_file, runner_id)
+    else {
+        eprintln!("Usage: website_checker runner --driver url --psk-file path --runner-id id");
+        std::process::exit(2);
+    };
+
+    let psk = read_psk(psk_file);
+    runner::run_runner(driver_url, &psk, runner_id);
+}
+
+fn read_psk(path: &str) -> Vec<u8> {
+    fs::read_to_string(path)
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to read PSK file {}: {}", path, e);
+            std::process::exit(2);
+        })
+        .trim()
+        .as_bytes()
+        .to_vec()
+}
+
+/// Handles the `history <url>` subcommand: prints every recorded check, newest first.
+fn run_history_command(db_path: &str, url: &str) -> Result<(), String> {
+    let db = DbCtx::open(db_path).map_err(|e| format!("Failed to open {}: {}", db_path, e))?;
+    let rows = db
+        .history(url)
+        .map_err(|e| format!("Failed to query history: {}", e))?;
+
+    if rows.is_empty() {
+        println!("No history recorded for {}", url);
+        return Ok(());
+    }
+
+    for row in &rows {
+        let outcome = match (&row.status_code, &row.error) {
+            (Some(code), _) => format!("HTTP {}", code),
+            (None, Some(err)) => format!("Error: {}", err),
+            (None, None) => "Error: unknown".to_string(),
+        };
+        println!(
+            "{} {} -> {} ({} ms)",
+            url, row.timestamp, outcome, row.response_time_ms
+        );
+    }
+    Ok(())
+}
+
+/// Handles the `uptime <url> --since <duration>` subcommand: prints the rolling uptime
+/// ratio and p50/p95 latency over the window.
+fn run_uptime_command(db_path: &str, url: &str, since: Duration) -> Result<(), String> {
+    let db = DbCtx::open(db_path).map_err(|e| format!("Failed to open {}: {}", db_path, e))?;
+    let since_secs = (SystemTime::now() - since)
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+
+    let rows = db
+        .checks_since(url, since_secs)
+        .map_err(|e| format!("Failed to query uptime: {}", e))?;
+
+    if rows.is_empty() {
+        println!("No checks recorded for {} in the requested window", url);
+        return Ok(());
+    }
+
+    let summary = dbctx::summarize_uptime(&rows);
+    println!(
+        "{}: uptime {:.2}% ({}/{} checks), p50 {} ms, p95 {} ms",
+        url,
+        summary.uptime_ratio * 100.0,
+        summary.successful_checks,
+        summary.total_checks,
+        summary.p50_ms,
+        summary.p95_ms
+    );
+    Ok(())
+}
+
+/// Everything a worker needs to check a URL and record/notify on the result, shared
+/// (behind `Arc`/`Clone`) across every worker thread in a run.
+struct CheckContext {
+    client: Arc<Client>,
+    timeout_secs: u64,
+    retries: u32,
+    db: Option<Arc<Mutex<DbCtx>>>,
+    notifier_config: Arc<Option<notifier::NotifierConfig>>,
+    last_state: Arc<Mutex<HashMap<String, State>>>,
+}
+
+/// Seeds a `last_state` map at startup from `db`'s most recent recorded check per URL,
+/// so a one-shot run (no `--watch`) sharing a `--db` with a prior invocation can still
+/// detect a transition on its one and only check of each URL -- without this, a fresh,
+/// empty in-memory map would make every one-shot check look like "no prior state" and
+/// `notify_all` could never fire outside `--watch`. Falls back to an empty map when
+/// `db` is `None`, matching the in-memory-only behavior for the no-`--db` case.
+fn seed_last_state(db: &Option<Arc<Mutex<DbCtx>>>, urls: &[String]) -> HashMap<String, State> {
+    let Some(db) = db else {
+        return HashMap::new();
+    };
+    let db = db.lock().unwrap();
+    urls.iter()
+        .filter_map(|url| {
+            let row = db.history(url).ok()?.into_iter().next()?;
+            let action_status = row
+                .status_code
+                .map(|c| Ok(c as u16))
+                .unwrap_or_else(|| Err(row.error.unwrap_or_default()));
+            Some((url.clone(), State::from_action_status(&action_status)))
+        })
+        .collect()
+}
+
+/// Runs one check, records it to the database (if configured), fires notifiers on a
+/// state transition, prints the result, and returns it. Shared by the one-shot worker
+/// loop and the `--watch` scheduler loop.
+///
+/// The previous state used to detect a transition is tracked in `ctx.last_state`, in
+/// memory, independent of `ctx.db` — notifications work the same whether or not `--db`
+/// is passed. `ctx.last_state` is seeded from `ctx.db` (see [`seed_last_state`]) at
+/// startup so this also works across separate one-shot runs that share a `--db`.
+fn check_and_record(worker_label: &str, url: &str, ctx: &CheckContext) -> WebsiteStatus {
+    let result = process_url(url, &ctx.client, ctx.timeout_secs, ctx.retries);
+
+    let new_state = State::from_action_status(&result.action_status);
+    let previous_state = ctx
+        .last_state
+        .lock()
+        .unwrap()
+        .insert(result.url.clone(), new_state);
+
+    if let Some(db) = &ctx.db {
+        db.lock()
+            .unwrap()
+            .record_check(
+                &result.url,
+                result.timestamp,
+                &result.action_status,
+                result.response_time,
+            )
+            .expect("Failed to record check in database");
+    }
+
+    if let (Some(config), Some(old_state)) = (ctx.notifier_config.as_ref(), previous_state) {
+        if new_state != old_state {
+            notifier::notify_all(config, &result.url, old_state, new_state, result.response_time);
+        }
+    }
+
+    match &result.action_status {
+        Ok(code) => println!(
+            "[{}] {} -> HTTP {} ({} ms)",
+            worker_label,
+            result.url,
+            code,
+            result.response_time.as_millis()
+        ),
+        Err(err) => println!(
+            "[{}] {} -> Error: {} ({} ms)",
+            worker_label,
+            result.url,
+            err,
+            result.response_time.as_millis()
+        ),
+    }
+
+    result
+}
+
+/// Runs `--watch` mode: each worker repeatedly pulls whichever URL is next due from the
+/// shared [`Scheduler`] and checks it, forever. Unlike the one-shot mode there is no
+/// final `results` vector; `status.json` is overwritten after every single check so
+/// readers always see the latest known state per URL.
+fn run_watch_mode(
+    config: Config,
+    client: Arc<Client>,
+    db: Option<Arc<Mutex<DbCtx>>>,
+    notifier_config: Arc<Option<notifier::NotifierConfig>>,
+) {
+    let scheduler = Arc::new(Scheduler::new(&config.urls, Duration::from_secs(config.interval_secs)));
+    let latest: Arc<Mutex<HashMap<String, WebsiteStatus>>> = Arc::new(Mutex::new(HashMap::new()));
+    let last_state = Arc::new(Mutex::new(seed_last_state(&db, &config.urls)));
+
+    let mut workers_vec = Vec::new();
+    for id in 0..config.workers {
+        let scheduler_clone = Arc::clone(&scheduler);
+        let ctx = CheckContext {
+            client: Arc::clone(&client),
+            timeout_secs: config.timeout_secs,
+            retries: config.retries,
+            db: db.clone(),
+            notifier_config: Arc::clone(&notifier_config),
+            last_state: Arc::clone(&last_state),
+        };
+        let latest_clone = Arc::clone(&latest);
+        let output_path = config.output_path.clone();
+        let format = config.format;
+        let worker_label = format!("Worker {}", id);
+
+        let handle = thread::spawn(move || {
+            while let Some(url) = scheduler_clone.next_job() {
+                let result = check_and_record(&worker_label, &url, &ctx);
+                scheduler_clone.record_result(&url, result.action_status.is_ok());
+
+                let mut latest = latest_clone.lock().unwrap();
+                latest.insert(result.url.clone(), result);
+                write_status_json(latest.values(), &output_path, format);
+            }
+        });
+
+        workers_vec.push(handle);
+    }
+
+    for handle in workers_vec {
+        handle.join().expect("Worker thread panicked");
+    }
 }
 
 fn main() {
-    let (urls, num_workers, timeout_secs, retries) = match parse_args() {
+    let raw_args: Vec<String> = env::args().skip(1).collect();
+    if raw_args.first().map(String::as_str) == Some("history") {
+        run_history_subcommand(&raw_args[1..]);
+        return;
+    }
+    if raw_args.first().map(String::as_str) == Some("uptime") {
+        run_uptime_subcommand(&raw_args[1..]);
+        return;
+    }
+    if raw_args.first().map(String::as_str) == Some("driver") {
+        run_driver_subcommand(&raw_args[1..]);
+        return;
+    }
+    if raw_args.first().map(String::as_str) == Some("runner") {
+        run_runner_subcommand(&raw_args[1..]);
+        return;
+    }
+
+    let config = match parse_args() {
         Ok(config) => config,
         Err(e) => {
             eprintln!("{}", e);
-            eprintln!("Usage: website_checker [--file path] [URL ...] [--workers N] [--timeout S] [--retries N]");
+            eprintln!("Usage: website_checker [--file path] [URL ...] [--workers N] [--timeout S] [--retries N] [--output path] [--format json|ndjson|csv|prometheus] [--db path.sqlite] [--config notifiers.toml] [--watch --interval S]");
             std::process::exit(2);
         }
     };
 
+    let notifier_config = config.config_path.as_deref().map(|path| {
+        notifier::load_config(path).unwrap_or_else(|e| {
+            eprintln!("Failed to load {}: {}", path, e);
+            std::process::exit(2);
+        })
+    });
+    let notifier_config = Arc::new(notifier_config);
+
     println!(
         "Starting with {} workers, timeout {}s, retries {}",
-        num_workers, timeout_secs, retries
+        config.workers, config.timeout_secs, config.retries
     );
 
-    let (tx, rx) = mpsc::channel::<String>();
-    let rx = Arc::new(Mutex::new(rx));
-    let results = Arc::new(Mutex::new(Vec::new()));
+    let db = config.db_path.as_deref().map(|path| {
+        Arc::new(Mutex::new(
+            DbCtx::open(path).expect("Failed to open --db database"),
+        ))
+    });
 
     let client = Client::builder()
-        .timeout(Duration::from_secs(timeout_secs))
+        .timeout(Duration::from_secs(config.timeout_secs))
         .build()
         .expect("Failed to build client");
     let client = Arc::new(client);
 
+    if config.watch {
+        println!(
+            "Watching {} URL(s) every {}s (Ctrl+C to stop)",
+            config.urls.len(),
+            config.interval_secs
+        );
+        run_watch_mode(config, client, db, notifier_config);
+        return;
+    }
+
+    let (tx, rx) = mpsc::channel::<String>();
+    let rx = Arc::new(Mutex::new(rx));
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let last_state = Arc::new(Mutex::new(seed_last_state(&db, &config.urls)));
+
+    let ndjson_file = if config.format == output::Format::Ndjson {
+        let file = output::open_ndjson(&config.output_path)
+            .unwrap_or_else(|e| panic!("Failed to open {}: {}", config.output_path, e));
+        Some(Arc::new(Mutex::new(file)))
+    } else {
+        None
+    };
+
     let mut workers_vec = Vec::new();
-    for id in 0..num_workers {
+    for id in 0..config.workers {
         let rx_clone = Arc::clone(&rx);
-        let client_clone = Arc::clone(&client);
+        let ctx = CheckContext {
+            client: Arc::clone(&client),
+            timeout_secs: config.timeout_secs,
+            retries: config.retries,
+            db: db.clone(),
+            notifier_config: Arc::clone(&notifier_config),
+            last_state: Arc::clone(&last_state),
+        };
         let results_clone = Arc::clone(&results);
+        let ndjson_file_clone = ndjson_file.clone();
+        let worker_label = format!("Worker {}", id);
 
         let handle = thread::spawn(move || {
             while let Ok(url) = rx_clone.lock().unwrap().recv() {
-                let result = process_url(&url, &client_clone, timeout_secs, retries);
-                match &result.action_status {
-                    Ok(code) => println!(
-                        "[Worker {}] {} -> HTTP {} ({} ms)",
-                        id,
-                        result.url,
-                        code,
-                        result.response_time.as_millis()
-                    ),
-                    Err(err) => println!(
-                        "[Worker {}] {} -> Error: {} ({} ms)",
-                        id,
-                        result.url,
-                        err,
-                        result.response_time.as_millis()
-                    ),
+                let result = check_and_record(&worker_label, &url, &ctx);
+
+                match &ndjson_file_clone {
+                    Some(file) => {
+                        let record = output::StatusRecord::from(&result);
+                        output::append_ndjson_line(&mut file.lock().unwrap(), &record)
+                            .expect("Failed to append NDJSON line");
+                    }
+                    None => results_clone.lock().unwrap().push(result),
                 }
-                results_clone.lock().unwrap().push(result);
             }
-            println!("[Worker {}] Exiting.", id);
+            println!("[{}] Exiting.", worker_label);
         });
 
         workers_vec.push(handle);
     }
 
-    for url in urls {
-        tx.send(url).expect("Failed to send job");
+    for url in &config.urls {
+        tx.send(url.clone()).expect("Failed to send job");
     }
     drop(tx);
 
@@ -210,6 +756,93 @@ fn main() {
     }
 
     println!("All checks complete.");
-    let results = results.lock().unwrap();
-    write_status_json(&results);
+    if ndjson_file.is_none() {
+        let results = results.lock().unwrap();
+        write_status_json(results.iter(), &config.output_path, config.format);
+    } else {
+        println!("Results streamed to {}", config.output_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unique scratch DB path per test run, cleaned up on drop.
+    struct TempDbPath(std::path::PathBuf);
+
+    impl TempDbPath {
+        fn new(label: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "website_checker_test_{}_{}_{:?}.sqlite",
+                label,
+                std::process::id(),
+                std::thread::current().id()
+            ));
+            let _ = std::fs::remove_file(&path);
+            TempDbPath(path)
+        }
+
+        fn as_str(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+
+    impl Drop for TempDbPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn last_state_seeds_empty_without_a_db() {
+        let last_state = seed_last_state(&None, &["https://example.com".to_string()]);
+        assert!(last_state.is_empty());
+    }
+
+    #[test]
+    fn last_state_seeds_from_db_history_when_present() {
+        let db_path = TempDbPath::new("seed");
+        let url = "https://example.com";
+
+        {
+            let db = DbCtx::open(db_path.as_str()).unwrap();
+            db.record_check(url, SystemTime::now(), &Ok(200), Duration::from_millis(5))
+                .unwrap();
+        }
+
+        let db = Some(Arc::new(Mutex::new(DbCtx::open(db_path.as_str()).unwrap())));
+        let last_state = seed_last_state(&db, &[url.to_string()]);
+        assert_eq!(last_state.get(url), Some(&State::Up));
+    }
+
+    #[test]
+    fn transition_is_detected_across_separate_one_shot_runs_sharing_a_db() {
+        let db_path = TempDbPath::new("transition");
+        let url = "https://example.com";
+
+        // Run 1: records a failing check, as check_and_record would via `&db`.
+        {
+            let db = DbCtx::open(db_path.as_str()).unwrap();
+            db.record_check(
+                url,
+                SystemTime::now(),
+                &Err("connection refused".to_string()),
+                Duration::from_millis(5),
+            )
+            .unwrap();
+        }
+
+        // Run 2: a fresh process starts with an empty in-memory last_state, but seeding
+        // it from --db recovers run 1's Down state, so a new Up check is correctly seen
+        // as a transition instead of "no prior state".
+        let db = Some(Arc::new(Mutex::new(DbCtx::open(db_path.as_str()).unwrap())));
+        let last_state = Arc::new(Mutex::new(seed_last_state(&db, &[url.to_string()])));
+
+        let new_state = State::Up;
+        let previous_state = last_state.lock().unwrap().insert(url.to_string(), new_state);
+
+        assert_eq!(previous_state, Some(State::Down));
+        assert_ne!(previous_state, Some(new_state));
+    }
 }
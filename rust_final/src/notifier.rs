@@ -0,0 +1,286 @@
+//! Outbound alerting for URL status transitions.
+//!
+//! Notifiers are configured via a TOML file (`--config notifiers.toml`) listing one or
+//! more sinks. Each sink carries its own `urls` filter, so different alert channels can
+//! watch different subsets of the checked URLs. The main loop only calls [`notify_all`]
+//! on a state transition (up->down or down->up) to avoid spamming on every check.
+
+use reqwest::blocking::Client;
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// Health state of a URL, derived from its most recent `action_status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Up,
+    Down,
+}
+
+impl State {
+    pub fn from_action_status(action_status: &Result<u16, String>) -> State {
+        match action_status {
+            Ok(code) if (200..400).contains(code) => State::Up,
+            _ => State::Down,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            State::Up => "up",
+            State::Down => "down",
+        }
+    }
+}
+
+pub struct WebhookSink {
+    pub endpoint: String,
+    pub urls: Vec<String>,
+}
+
+pub struct EmailSink {
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub from: String,
+    pub to: String,
+    pub urls: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct NotifierConfig {
+    pub webhooks: Vec<WebhookSink>,
+    pub emails: Vec<EmailSink>,
+}
+
+/// Loads a `notifiers.toml` config. Expected shape:
+///
+/// ```toml
+/// [[webhook]]
+/// endpoint = "https://example.com/hook"
+/// urls = ["https://example.com"]
+///
+/// [[email]]
+/// smtp_host = "smtp.example.com"
+/// smtp_port = 587
+/// from = "alerts@example.com"
+/// to = "oncall@example.com"
+/// urls = ["https://example.com"]
+/// ```
+pub fn load_config(path: &str) -> Result<NotifierConfig, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let parsed: toml::Value =
+        content.parse().map_err(|e| format!("Invalid TOML in {}: {}", path, e))?;
+
+    let mut config = NotifierConfig::default();
+
+    if let Some(webhooks) = parsed.get("webhook").and_then(|v| v.as_array()) {
+        for entry in webhooks {
+            let endpoint = entry
+                .get("endpoint")
+                .and_then(|v| v.as_str())
+                .ok_or("webhook sink missing \"endpoint\"")?
+                .to_string();
+            let urls = string_array(entry, "urls");
+            config.webhooks.push(WebhookSink { endpoint, urls });
+        }
+    }
+
+    if let Some(emails) = parsed.get("email").and_then(|v| v.as_array()) {
+        for entry in emails {
+            let smtp_host = entry
+                .get("smtp_host")
+                .and_then(|v| v.as_str())
+                .ok_or("email sink missing \"smtp_host\"")?
+                .to_string();
+            let smtp_port = entry
+                .get("smtp_port")
+                .and_then(|v| v.as_integer())
+                .unwrap_or(25) as u16;
+            let from = entry
+                .get("from")
+                .and_then(|v| v.as_str())
+                .ok_or("email sink missing \"from\"")?
+                .to_string();
+            let to = entry
+                .get("to")
+                .and_then(|v| v.as_str())
+                .ok_or("email sink missing \"to\"")?
+                .to_string();
+            let urls = string_array(entry, "urls");
+            config.emails.push(EmailSink {
+                smtp_host,
+                smtp_port,
+                from,
+                to,
+                urls,
+            });
+        }
+    }
+
+    Ok(config)
+}
+
+fn string_array(entry: &toml::Value, key: &str) -> Vec<String> {
+    entry
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether a sink's `urls` filter covers `url` -- an empty filter matches every URL.
+fn url_matches(filter: &[String], url: &str) -> bool {
+    filter.is_empty() || filter.iter().any(|u| u == url)
+}
+
+/// Notifies every sink whose `urls` filter matches `url` (an empty filter matches all
+/// URLs) that it transitioned from `old_state` to `new_state`.
+pub fn notify_all(
+    config: &NotifierConfig,
+    url: &str,
+    old_state: State,
+    new_state: State,
+    response_time: Duration,
+) {
+    for sink in &config.webhooks {
+        if url_matches(&sink.urls, url) {
+            if let Err(e) = send_webhook(sink, url, old_state, new_state, response_time) {
+                eprintln!("Notifier: webhook to {} failed: {}", sink.endpoint, e);
+            }
+        }
+    }
+    for sink in &config.emails {
+        if url_matches(&sink.urls, url) {
+            if let Err(e) = send_email(sink, url, old_state, new_state, response_time) {
+                eprintln!("Notifier: email via {} failed: {}", sink.smtp_host, e);
+            }
+        }
+    }
+}
+
+/// Wire shape of a webhook alert, serialized with `serde_json` so a URL or state label
+/// containing a quote or control character can't corrupt or inject into the JSON body.
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    url: &'a str,
+    old_state: &'a str,
+    new_state: &'a str,
+    response_time_ms: u128,
+}
+
+fn send_webhook(
+    sink: &WebhookSink,
+    url: &str,
+    old_state: State,
+    new_state: State,
+    response_time: Duration,
+) -> Result<(), String> {
+    let payload = WebhookPayload {
+        url,
+        old_state: old_state.label(),
+        new_state: new_state.label(),
+        response_time_ms: response_time.as_millis(),
+    };
+    let body = serde_json::to_string(&payload).map_err(|e| e.to_string())?;
+
+    let client = Client::new();
+    client
+        .post(&sink.endpoint)
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Sends a bare-bones plaintext alert over SMTP without authentication (suitable for a
+/// local relay or an internal mail server that trusts the sending host).
+fn send_email(
+    sink: &EmailSink,
+    url: &str,
+    old_state: State,
+    new_state: State,
+    response_time: Duration,
+) -> Result<(), String> {
+    let stream = TcpStream::connect((sink.smtp_host.as_str(), sink.smtp_port))
+        .map_err(|e| e.to_string())?;
+    let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream);
+
+    let read_response = |reader: &mut BufReader<TcpStream>| -> Result<(), String> {
+        let mut line = String::new();
+        reader.read_line(&mut line).map_err(|e| e.to_string())?;
+        Ok(())
+    };
+
+    read_response(&mut reader)?; // greeting
+    writer
+        .write_all(b"HELO localhost\r\n")
+        .map_err(|e| e.to_string())?;
+    read_response(&mut reader)?;
+
+    writer
+        .write_all(format!("MAIL FROM:<{}>\r\n", sink.from).as_bytes())
+        .map_err(|e| e.to_string())?;
+    read_response(&mut reader)?;
+
+    writer
+        .write_all(format!("RCPT TO:<{}>\r\n", sink.to).as_bytes())
+        .map_err(|e| e.to_string())?;
+    read_response(&mut reader)?;
+
+    writer.write_all(b"DATA\r\n").map_err(|e| e.to_string())?;
+    read_response(&mut reader)?;
+
+    let body = format!(
+        "Subject: {} is {}\r\n\r\n{} transitioned from {} to {} ({} ms response time)\r\n.\r\n",
+        url,
+        new_state.label(),
+        url,
+        old_state.label(),
+        new_state.label(),
+        response_time.as_millis()
+    );
+    writer
+        .write_all(body.as_bytes())
+        .map_err(|e| e.to_string())?;
+    read_response(&mut reader)?;
+
+    writer.write_all(b"QUIT\r\n").map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_filter_matches_every_url() {
+        assert!(url_matches(&[], "https://example.com"));
+        assert!(url_matches(&[], "https://example.org"));
+    }
+
+    #[test]
+    fn non_empty_filter_matches_only_listed_urls() {
+        let filter = vec!["https://example.com".to_string()];
+        assert!(url_matches(&filter, "https://example.com"));
+        assert!(!url_matches(&filter, "https://example.org"));
+    }
+
+    #[test]
+    fn from_action_status_treats_2xx_3xx_as_up() {
+        assert_eq!(State::from_action_status(&Ok(200)), State::Up);
+        assert_eq!(State::from_action_status(&Ok(301)), State::Up);
+        assert_eq!(State::from_action_status(&Ok(404)), State::Down);
+        assert_eq!(
+            State::from_action_status(&Err("connection refused".to_string())),
+            State::Down
+        );
+    }
+}
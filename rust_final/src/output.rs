@@ -0,0 +1,292 @@
+//! Structured, multi-format result output.
+//!
+//! [`WebsiteStatus`] is rendered through [`StatusRecord`], a small serde-friendly view
+//! that turns its `Duration`/`SystemTime` fields into plain milliseconds/Unix-seconds.
+//! `--format json` renders the whole list via `serde_json`; `csv` and `prometheus` also
+//! render the whole list, just in their own shape; in a one-shot run `ndjson` is written
+//! one line at a time as each check completes (see [`append_ndjson_line`]) so a
+//! long-running check never has to hold every result in memory at once. `--watch` and
+//! the driver render `ndjson` for the whole list through [`render`] like the others.
+//!
+//! The driver's aggregated `JobResult`s also render through [`StatusRecord`] (via `From<&
+//! JobResult>`), carrying a `runner_id` the single-machine path leaves `None`, so every
+//! output path shares one flat schema.
+
+use crate::protocol::JobResult;
+use crate::WebsiteStatus;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Ndjson,
+    Csv,
+    Prometheus,
+}
+
+impl Format {
+    pub fn parse(input: &str) -> Result<Format, String> {
+        match input {
+            "json" => Ok(Format::Json),
+            "ndjson" => Ok(Format::Ndjson),
+            "csv" => Ok(Format::Csv),
+            "prometheus" => Ok(Format::Prometheus),
+            other => Err(format!(
+                "Unknown --format '{}' (expected json, ndjson, csv, or prometheus)",
+                other
+            )),
+        }
+    }
+}
+
+/// A flat, serializable view of a [`WebsiteStatus`] check result (or, with `runner_id`
+/// set, a driver-aggregated [`JobResult`]).
+#[derive(Debug)]
+pub struct StatusRecord {
+    pub url: String,
+    pub status: Result<u16, String>,
+    pub response_time_ms: u128,
+    pub timestamp: u64,
+    pub runner_id: Option<String>,
+}
+
+/// Serializes `status` as a bare value (the HTTP code, or the error string) rather than
+/// serde's default externally-tagged `{"Ok": ...}` / `{"Err": ...}` shape, so `--format
+/// json` keeps the flat `"status": 200` / `"status": "error"` shape status.json has always
+/// had, matching how `render_csv`/`render_prometheus` already flatten it below.
+/// `runner_id` is omitted entirely (not even `null`) when absent, so the single-machine
+/// output shape is unchanged.
+impl Serialize for StatusRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let len = if self.runner_id.is_some() { 5 } else { 4 };
+        let mut state = serializer.serialize_struct("StatusRecord", len)?;
+        state.serialize_field("url", &self.url)?;
+        match &self.status {
+            Ok(code) => state.serialize_field("status", code)?,
+            Err(err) => state.serialize_field("status", err)?,
+        }
+        state.serialize_field("response_time_ms", &self.response_time_ms)?;
+        state.serialize_field("timestamp", &self.timestamp)?;
+        if let Some(runner_id) = &self.runner_id {
+            state.serialize_field("runner_id", runner_id)?;
+        }
+        state.end()
+    }
+}
+
+impl From<&WebsiteStatus> for StatusRecord {
+    fn from(status: &WebsiteStatus) -> Self {
+        StatusRecord {
+            url: status.url.clone(),
+            status: status.action_status.clone(),
+            response_time_ms: status.response_time.as_millis(),
+            timestamp: status
+                .timestamp
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            runner_id: None,
+        }
+    }
+}
+
+impl From<&JobResult> for StatusRecord {
+    fn from(result: &JobResult) -> Self {
+        StatusRecord {
+            url: result.url.clone(),
+            status: result.status.clone(),
+            response_time_ms: result.response_time_ms as u128,
+            timestamp: result.timestamp,
+            runner_id: Some(result.runner_id.clone()),
+        }
+    }
+}
+
+/// Renders the full result set `records` in `format`. For `ndjson` this is one line per
+/// record, used by `--watch` and the driver; a one-shot run streams `ndjson` through
+/// [`append_ndjson_line`] as each check completes instead.
+pub fn render(records: &[StatusRecord], format: Format) -> String {
+    match format {
+        Format::Json => serde_json::to_string_pretty(records).expect("records always serialize"),
+        Format::Ndjson => records
+            .iter()
+            .map(render_ndjson_line)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Format::Csv => render_csv(records),
+        Format::Prometheus => render_prometheus(records),
+    }
+}
+
+fn render_ndjson_line(record: &StatusRecord) -> String {
+    serde_json::to_string(record).expect("record always serializes")
+}
+
+fn render_csv(records: &[StatusRecord]) -> String {
+    let with_runner = records.iter().any(|r| r.runner_id.is_some());
+    let mut csv = if with_runner {
+        String::from("url,status,response_time_ms,timestamp,runner_id\n")
+    } else {
+        String::from("url,status,response_time_ms,timestamp\n")
+    };
+    for record in records {
+        // Commas are the column separator, so they're replaced with semicolons in every
+        // free-text field (url, error message, runner id) rather than fully CSV-quoted.
+        let url = record.url.replace(',', ";");
+        let status = match &record.status {
+            Ok(code) => code.to_string(),
+            Err(err) => err.replace(',', ";"),
+        };
+        if with_runner {
+            let runner_id = record.runner_id.as_deref().unwrap_or("").replace(',', ";");
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                url, status, record.response_time_ms, record.timestamp, runner_id
+            ));
+        } else {
+            csv.push_str(&format!(
+                "{},{},{},{}\n",
+                url, status, record.response_time_ms, record.timestamp
+            ));
+        }
+    }
+    csv
+}
+
+fn render_prometheus(records: &[StatusRecord]) -> String {
+    let mut out = String::new();
+    for record in records {
+        let up = if matches!(record.status, Ok(code) if (200..400).contains(&code)) {
+            1
+        } else {
+            0
+        };
+        let labels = match &record.runner_id {
+            Some(runner_id) => format!("url=\"{}\",runner_id=\"{}\"", record.url, runner_id),
+            None => format!("url=\"{}\"", record.url),
+        };
+        out.push_str(&format!("website_up{{{}}} {}\n", labels, up));
+        out.push_str(&format!(
+            "website_response_time_ms{{{}}} {}\n",
+            labels, record.response_time_ms
+        ));
+    }
+    out
+}
+
+/// Opens `path` for a fresh NDJSON stream, truncating any previous content.
+pub fn open_ndjson(path: &str) -> std::io::Result<File> {
+    OpenOptions::new().create(true).write(true).truncate(true).open(path)
+}
+
+/// Appends one NDJSON line for `record` to an already-open stream.
+pub fn append_ndjson_line(file: &mut File, record: &StatusRecord) -> std::io::Result<()> {
+    writeln!(file, "{}", render_ndjson_line(record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok_record() -> StatusRecord {
+        StatusRecord {
+            url: "https://example.com".to_string(),
+            status: Ok(200),
+            response_time_ms: 42,
+            timestamp: 1_700_000_000,
+            runner_id: None,
+        }
+    }
+
+    fn err_record() -> StatusRecord {
+        StatusRecord {
+            url: "https://example.org".to_string(),
+            status: Err("connection refused".to_string()),
+            response_time_ms: 7,
+            timestamp: 1_700_000_001,
+            runner_id: None,
+        }
+    }
+
+    #[test]
+    fn json_keeps_status_flat() {
+        let rendered = render(&[ok_record(), err_record()], Format::Json);
+        assert!(rendered.contains("\"status\": 200"));
+        assert!(rendered.contains("\"status\": \"connection refused\""));
+    }
+
+    #[test]
+    fn ndjson_renders_one_object_per_line() {
+        let rendered = render(&[ok_record(), err_record()], Format::Ndjson);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("\"status\":200"));
+        assert!(lines[1].contains("\"status\":\"connection refused\""));
+    }
+
+    #[test]
+    fn csv_escapes_commas_in_errors() {
+        let mut record = err_record();
+        record.status = Err("timed out, retrying".to_string());
+        let rendered = render(&[record], Format::Csv);
+        assert_eq!(
+            rendered,
+            "url,status,response_time_ms,timestamp\nhttps://example.org,timed out; retrying,7,1700000001\n"
+        );
+    }
+
+    #[test]
+    fn csv_escapes_commas_in_urls() {
+        let mut record = ok_record();
+        record.url = "https://example.com/search?ids=1,2,3".to_string();
+        let rendered = render(&[record], Format::Csv);
+        assert_eq!(
+            rendered,
+            "url,status,response_time_ms,timestamp\nhttps://example.com/search?ids=1;2;3,200,42,1700000000\n"
+        );
+    }
+
+    #[test]
+    fn csv_adds_runner_id_column_when_present() {
+        let mut record = ok_record();
+        record.runner_id = Some("runner-a".to_string());
+        let rendered = render(&[record], Format::Csv);
+        assert_eq!(
+            rendered,
+            "url,status,response_time_ms,timestamp,runner_id\nhttps://example.com,200,42,1700000000,runner-a\n"
+        );
+    }
+
+    #[test]
+    fn prometheus_marks_2xx_3xx_as_up() {
+        let rendered = render(&[ok_record(), err_record()], Format::Prometheus);
+        assert!(rendered.contains("website_up{url=\"https://example.com\"} 1"));
+        assert!(rendered.contains("website_up{url=\"https://example.org\"} 0"));
+    }
+
+    #[test]
+    fn prometheus_includes_runner_id_label_when_present() {
+        let mut record = ok_record();
+        record.runner_id = Some("runner-a".to_string());
+        let rendered = render(&[record], Format::Prometheus);
+        assert!(rendered.contains("website_up{url=\"https://example.com\",runner_id=\"runner-a\"} 1"));
+    }
+
+    #[test]
+    fn json_omits_runner_id_when_absent() {
+        let rendered = render(&[ok_record()], Format::Json);
+        assert!(!rendered.contains("runner_id"));
+    }
+
+    #[test]
+    fn format_parse_rejects_unknown_values() {
+        assert!(Format::parse("yaml").is_err());
+        assert_eq!(Format::parse("csv"), Ok(Format::Csv));
+    }
+}
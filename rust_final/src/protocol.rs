@@ -0,0 +1,87 @@
+//! Wire types and HMAC authentication shared between the `driver` and `runner` roles.
+//!
+//! Every request body is signed with HMAC-SHA256 over a shared pre-shared key (PSK) so a
+//! driver only hands jobs to, and accepts results from, runners that hold the same secret.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A single URL check handed from the driver to a runner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestedJob {
+    pub url: String,
+    pub timeout_secs: u64,
+    pub retries: u32,
+}
+
+/// A completed check POSTed back from a runner to the driver.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobResult {
+    pub url: String,
+    pub status: Result<u16, String>,
+    pub response_time_ms: u64,
+    pub timestamp: u64,
+    pub runner_id: String,
+}
+
+/// Computes the hex-encoded HMAC-SHA256 signature of `body` under `psk`.
+pub fn sign(psk: &[u8], body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(psk).expect("HMAC accepts a key of any length");
+    mac.update(body);
+    to_hex(&mac.finalize().into_bytes())
+}
+
+/// Checks `signature_hex` against the HMAC-SHA256 of `body` under `psk`, comparing in
+/// constant time so a mismatch can't be used to probe the signature byte-by-byte.
+pub fn verify(psk: &[u8], body: &[u8], signature_hex: &str) -> bool {
+    let expected = sign(psk, body);
+    constant_time_eq(expected.as_bytes(), signature_hex.as_bytes())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_accepts_a_signature_from_sign() {
+        let psk = b"shared-secret";
+        let body = b"{\"url\":\"https://example.com\"}";
+        let signature = sign(psk, body);
+        assert!(verify(psk, body, &signature));
+    }
+
+    #[test]
+    fn verify_rejects_a_tampered_body() {
+        let psk = b"shared-secret";
+        let signature = sign(psk, b"original");
+        assert!(!verify(psk, b"tampered", &signature));
+    }
+
+    #[test]
+    fn verify_rejects_the_wrong_key() {
+        let body = b"payload";
+        let signature = sign(b"key-one", body);
+        assert!(!verify(b"key-two", body, &signature));
+    }
+
+    #[test]
+    fn sign_is_deterministic() {
+        let psk = b"shared-secret";
+        let body = b"payload";
+        assert_eq!(sign(psk, body), sign(psk, body));
+    }
+}
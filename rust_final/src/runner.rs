@@ -0,0 +1,75 @@
+//! Remote-runner side of the distributed driver/runner architecture.
+//!
+//! A runner long-polls the driver for jobs, checks them locally with the same
+//! [`crate::process_url`] used everywhere else in this tool, and POSTs the result back,
+//! signing every request body with the shared pre-shared key.
+
+use crate::protocol::{self, JobResult, RequestedJob};
+use crate::process_url;
+use reqwest::blocking::Client;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How long to wait before re-polling after the driver reports no job available.
+const EMPTY_POLL_DELAY: Duration = Duration::from_secs(2);
+
+/// Long-polls `driver_url` for jobs and reports results under `runner_id`, forever.
+pub fn run_runner(driver_url: &str, psk: &[u8], runner_id: &str) {
+    let client = Client::new();
+
+    loop {
+        match fetch_job(&client, driver_url, psk) {
+            Some(job) => {
+                let result = process_url(&job.url, &client, job.timeout_secs, job.retries);
+                let job_result = JobResult {
+                    url: result.url,
+                    status: result.action_status,
+                    response_time_ms: result.response_time.as_millis() as u64,
+                    timestamp: result
+                        .timestamp
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs(),
+                    runner_id: runner_id.to_string(),
+                };
+                if let Err(e) = submit_result(&client, driver_url, psk, &job_result) {
+                    eprintln!("Runner: failed to submit result: {}", e);
+                }
+            }
+            None => thread::sleep(EMPTY_POLL_DELAY),
+        }
+    }
+}
+
+fn fetch_job(client: &Client, driver_url: &str, psk: &[u8]) -> Option<RequestedJob> {
+    let signature = protocol::sign(psk, &[]);
+    let response = client
+        .get(format!("{}/job", driver_url))
+        .header("X-Signature", signature)
+        .send()
+        .ok()?;
+
+    if response.status().as_u16() != 200 {
+        return None;
+    }
+    response.json::<RequestedJob>().ok()
+}
+
+fn submit_result(
+    client: &Client,
+    driver_url: &str,
+    psk: &[u8],
+    job_result: &JobResult,
+) -> Result<(), String> {
+    let body = serde_json::to_vec(job_result).map_err(|e| e.to_string())?;
+    let signature = protocol::sign(psk, &body);
+
+    client
+        .post(format!("{}/result", driver_url))
+        .header("X-Signature", signature)
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
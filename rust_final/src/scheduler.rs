@@ -0,0 +1,88 @@
+//! Per-URL scheduling for `--watch` mode.
+//!
+//! Each URL carries its own `next_update: Instant`. A successful check resets it to the
+//! normal polling interval; a failed check applies exponential backoff (capped) with
+//! jitter so repeatedly-failing URLs back off instead of hammering a dead endpoint.
+//! Workers pull whichever URL is due next rather than draining a one-time job channel.
+
+use crate::backoff;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const RETRY_BACKOFF_BASE: Duration = Duration::from_secs(30);
+const RETRY_BACKOFF_CAP: Duration = Duration::from_secs(60 * 60);
+
+/// How often the scheduler re-checks the heap while waiting for the next due job.
+const POLL_GRANULARITY: Duration = Duration::from_millis(500);
+
+pub struct Scheduler {
+    heap: Mutex<BinaryHeap<(Reverse<Instant>, String)>>,
+    consecutive_failures: Mutex<HashMap<String, u32>>,
+    interval: Duration,
+}
+
+impl Scheduler {
+    /// Builds a scheduler with every URL due immediately.
+    pub fn new(urls: &[String], interval: Duration) -> Self {
+        let now = Instant::now();
+        let heap = urls.iter().map(|url| (Reverse(now), url.clone())).collect();
+        Scheduler {
+            heap: Mutex::new(heap),
+            consecutive_failures: Mutex::new(HashMap::new()),
+            interval,
+        }
+    }
+
+    /// Blocks until a URL is due, then returns it. Never returns `None`: every URL is
+    /// rescheduled after each check via [`Scheduler::record_result`], but the heap is
+    /// briefly empty whenever every URL is currently checked out by some worker (common
+    /// when `workers >= urls.len()`), so an empty heap just means "keep waiting", not
+    /// "nothing left to do".
+    pub fn next_job(&self) -> Option<String> {
+        loop {
+            let mut heap = self.heap.lock().unwrap();
+            match heap.peek() {
+                Some(&(Reverse(next_update), _)) => {
+                    let now = Instant::now();
+                    if next_update <= now {
+                        let (_, url) = heap.pop().unwrap();
+                        return Some(url);
+                    }
+                    drop(heap);
+                    thread::sleep((next_update - now).min(POLL_GRANULARITY));
+                }
+                None => {
+                    drop(heap);
+                    thread::sleep(POLL_GRANULARITY);
+                }
+            }
+        }
+    }
+
+    /// Reschedules `url` after a check: back to the normal interval on success, or with
+    /// exponential backoff (capped, jittered) on failure.
+    pub fn record_result(&self, url: &str, success: bool) {
+        let mut failures = self.consecutive_failures.lock().unwrap();
+        let next_update = if success {
+            failures.insert(url.to_string(), 0);
+            Instant::now() + self.interval
+        } else {
+            let count = failures.entry(url.to_string()).or_insert(0);
+            let delay = backoff::with_jitter(backoff::exponential_delay(
+                *count,
+                RETRY_BACKOFF_BASE,
+                RETRY_BACKOFF_CAP,
+            ));
+            *count += 1;
+            Instant::now() + delay
+        };
+        drop(failures);
+        self.heap
+            .lock()
+            .unwrap()
+            .push((Reverse(next_update), url.to_string()));
+    }
+}